@@ -0,0 +1,269 @@
+use crate::{Deserialize, QuadTree, QueryRegion, Rectangle, Scalar, Serialize, Vector};
+
+// Index of node `i`'s four children in the flat `nodes` vec.
+fn child_indices(i: usize) -> [usize; 4] {
+    [4 * i + 1, 4 * i + 2, 4 * i + 3, 4 * i + 4]
+}
+
+// Index of node `i`'s parent in the flat `nodes` vec.
+#[allow(dead_code)]
+fn parent_index(i: usize) -> usize {
+    (i - 1) / 4
+}
+
+// A single node in the arena. Children, when present, live at
+// `child_indices(self_index)` in the tree's `nodes` vec rather than behind a
+// pointer; points stored directly at this node are recorded as indices into
+// the tree's `id`/`loc`/`data` arrays.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Node<S> {
+    boundary: Rectangle<S>,
+    point_indices: Vec<usize>,
+    has_children: bool,
+}
+
+impl<S: Scalar> Node<S> {
+    fn new(boundary: Rectangle<S>) -> Self {
+        Self {
+            boundary,
+            point_indices: Vec::new(),
+            has_children: false,
+        }
+    }
+}
+
+/// A cache-friendly alternative to `QuadTree`. Instead of `Option<[Box<QuadTree>; 4]>`
+/// children, every node lives in a single flat `Vec` addressed by index: node `i`'s
+/// children sit at `4*i+1..=4*i+4` and its parent at `(i-1)/4`. Points are kept in a
+/// struct-of-arrays layout (`id`, `loc`, `data`) instead of one `Vec<T>` per node, so a
+/// scan over a node's points touches only the small `loc` slice before ever looking at
+/// `data`. Use this over `QuadTree` when traversal performance on large trees matters
+/// more than the ergonomics of the pointer-based tree; convert between the two with
+/// `From`/`Into` to pick whichever layout fits a given workload.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArenaQuadTree<T, S = u32>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    capacity: usize,
+    nodes: Vec<Option<Node<S>>>,
+    id: Vec<usize>,
+    loc: Vec<(S, S)>,
+    data: Vec<T>,
+    next_id: usize,
+}
+
+impl<T, S> ArenaQuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    /// Create a new ArenaQuadTree object with a boundary and a capacity.
+    pub fn new(boundary: Rectangle<S>, capacity: usize) -> Self {
+        Self {
+            capacity,
+            nodes: vec![Some(Node::new(boundary))],
+            id: Vec::new(),
+            loc: Vec::new(),
+            data: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// The root node's boundary.
+    pub fn boundary(&self) -> Rectangle<S> {
+        self.nodes[0].as_ref().expect("root node always exists").boundary
+    }
+
+    /// The per-node point capacity before a node subdivides.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // When a node's capacity is reached, subdivide is called to create children,
+    // growing `nodes` to make room for them if needed.
+    fn subdivide(&mut self, idx: usize) {
+        let boundary = self.nodes[idx].as_ref().expect("node exists").boundary;
+
+        let x = boundary.x;
+        let y = boundary.y;
+        let w = boundary.w.half();
+        let h = boundary.h.half();
+        // The east/south quadrants take whatever is left over so an odd width or
+        // height still tiles the parent exactly, with no gap column/row dropped.
+        let w2 = boundary.w - w;
+        let h2 = boundary.h - h;
+
+        let quadrants = [
+            Rectangle::new(x, y, w, h),
+            Rectangle::new(x + w, y, w2, h),
+            Rectangle::new(x, y + h, w, h2),
+            Rectangle::new(x + w, y + h, w2, h2),
+        ];
+
+        let children = child_indices(idx);
+        let needed = children[3] + 1;
+        if self.nodes.len() < needed {
+            self.nodes.resize_with(needed, || None);
+        }
+        for (child_idx, quadrant) in children.into_iter().zip(quadrants) {
+            self.nodes[child_idx] = Some(Node::new(quadrant));
+        }
+
+        self.nodes[idx].as_mut().expect("node exists").has_children = true;
+    }
+
+    /// Will not overwrite same location.
+    pub fn insert(&mut self, item: &T) -> bool {
+        self.insert_at(0, item)
+    }
+
+    fn insert_at(&mut self, idx: usize, item: &T) -> bool {
+        let point = item.as_point();
+        let Some(node) = self.nodes[idx].as_ref() else {
+            return false;
+        };
+        if !node.boundary.contains_point(point) {
+            return false;
+        }
+
+        if !node.has_children {
+            if node.point_indices.iter().any(|&gi| self.data[gi] == *item) {
+                return false;
+            }
+            if node.point_indices.len() < self.capacity {
+                let gi = self.data.len();
+                self.id.push(self.next_id);
+                self.next_id += 1;
+                self.loc.push(point);
+                self.data.push(item.clone());
+                self.nodes[idx]
+                    .as_mut()
+                    .expect("node exists")
+                    .point_indices
+                    .push(gi);
+                return true;
+            }
+            self.subdivide(idx);
+        }
+
+        child_indices(idx).into_iter().any(|child| self.insert_at(child, item))
+    }
+
+    /// Removes the first item equal to `item`.
+    pub fn remove(&mut self, item: &T) -> bool {
+        self.remove_at(0, item)
+    }
+
+    fn remove_at(&mut self, idx: usize, item: &T) -> bool {
+        if idx >= self.nodes.len() {
+            return false;
+        }
+        let found = match self.nodes[idx].as_ref() {
+            Some(node) => node
+                .point_indices
+                .iter()
+                .position(|&gi| self.data[gi] == *item),
+            None => return false,
+        };
+
+        if let Some(pos) = found {
+            let gi = self.nodes[idx]
+                .as_mut()
+                .expect("node exists")
+                .point_indices
+                .remove(pos);
+            self.remove_global(gi);
+            return true;
+        }
+
+        let has_children = self.nodes[idx].as_ref().expect("node exists").has_children;
+        if has_children {
+            return child_indices(idx).into_iter().any(|child| self.remove_at(child, item));
+        }
+        false
+    }
+
+    // Removes global point `gi` from the `id`/`loc`/`data` arrays and fixes up every
+    // node's `point_indices` that pointed past it, since removal shifts later entries down.
+    fn remove_global(&mut self, gi: usize) {
+        self.id.remove(gi);
+        self.loc.remove(gi);
+        self.data.remove(gi);
+        for node in self.nodes.iter_mut().flatten() {
+            for point_idx in node.point_indices.iter_mut() {
+                if *point_idx > gi {
+                    *point_idx -= 1;
+                }
+            }
+        }
+    }
+
+    /// Can pull out Points from any `QueryRegion` area, e.g. a `Rectangle` or a `Circle`.
+    /// A `None` range visits every point in the tree.
+    pub fn query<R: QueryRegion<S>, F: FnMut(&T)>(&self, range: Option<&R>, func: &mut F) {
+        self.query_at(0, range, func);
+    }
+
+    fn query_at<R: QueryRegion<S>, F: FnMut(&T)>(&self, idx: usize, range: Option<&R>, func: &mut F) {
+        let Some(node) = self.nodes.get(idx).and_then(|n| n.as_ref()) else {
+            return;
+        };
+        if let Some(range) = range {
+            if !range.intersects_rect(&node.boundary) {
+                return;
+            }
+        }
+
+        for &gi in &node.point_indices {
+            if range.is_none_or(|range| range.contains_point(self.loc[gi])) {
+                func(&self.data[gi]);
+            }
+        }
+
+        if node.has_children {
+            for child in child_indices(idx) {
+                self.query_at(child, range, func);
+            }
+        }
+    }
+
+    /// Return the total number of items in the ArenaQuadTree.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Return `true` if empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl<T, S> From<&QuadTree<T, S>> for ArenaQuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    fn from(tree: &QuadTree<T, S>) -> Self {
+        let mut arena = ArenaQuadTree::new(tree.boundary(), tree.capacity());
+        tree.query(None::<&Rectangle<S>>, &mut |item: &T| {
+            arena.insert(item);
+        });
+        arena
+    }
+}
+
+impl<T, S> From<&ArenaQuadTree<T, S>> for QuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    fn from(arena: &ArenaQuadTree<T, S>) -> Self {
+        let mut tree = QuadTree::new(arena.boundary(), arena.capacity());
+        arena.query(None::<&Rectangle<S>>, &mut |item: &T| {
+            tree.insert(item);
+        });
+        tree
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{Deserialize, QueryRegion, Rectangle, Scalar, Serialize};
+
+/// A trait for items that occupy an area rather than a single point, e.g. a
+/// sprite or a collision volume.
+pub trait Bounded<S: Scalar = u32>: Clone + PartialEq + std::fmt::Debug {
+    /// The item's bounding rectangle.
+    fn bounds(&self) -> Rectangle<S>;
+}
+
+/// A quadtree that indexes items by their bounding `Rectangle` instead of a
+/// single point, the standard "loose" rectangle-item quadtree used for
+/// broad-phase collision detection. An item is inserted into the deepest
+/// existing node whose `boundary` fully contains its bounds; an item that
+/// straddles a split line doesn't fully fit in any child, so it's kept at the
+/// parent level instead. Items a node already holds when it subdivides stay
+/// at that node; they are not moved down into the new children.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BoundsQuadTree<T, S = u32>
+where
+    T: Bounded<S>,
+    S: Scalar,
+{
+    boundary: Rectangle<S>,
+    capacity: usize,
+    items: Vec<T>,
+    children: Option<[Box<BoundsQuadTree<T, S>>; 4]>,
+}
+
+impl<T, S> BoundsQuadTree<T, S>
+where
+    T: Bounded<S>,
+    S: Scalar,
+{
+    /// Create a new BoundsQuadTree object with a boundary and a capacity.
+    pub fn new(boundary: Rectangle<S>, capacity: usize) -> Self {
+        Self {
+            boundary,
+            capacity,
+            items: Vec::with_capacity(capacity),
+            children: None,
+        }
+    }
+
+    // When a node's capacity is reached, subdivide is called to create children.
+    fn subdivide(boundary: Rectangle<S>, capacity: usize) -> [Box<BoundsQuadTree<T, S>>; 4] {
+        let x = boundary.x;
+        let y = boundary.y;
+        let w = boundary.w.half();
+        let h = boundary.h.half();
+        // The east/south quadrants take whatever is left over so an odd width or
+        // height still tiles the parent exactly, with no gap column/row dropped.
+        let w2 = boundary.w - w;
+        let h2 = boundary.h - h;
+
+        let nw = Rectangle::new(x, y, w, h);
+        let ne = Rectangle::new(x + w, y, w2, h);
+        let sw = Rectangle::new(x, y + h, w, h2);
+        let se = Rectangle::new(x + w, y + h, w2, h2);
+
+        [
+            Box::new(BoundsQuadTree::new(nw, capacity)),
+            Box::new(BoundsQuadTree::new(ne, capacity)),
+            Box::new(BoundsQuadTree::new(sw, capacity)),
+            Box::new(BoundsQuadTree::new(se, capacity)),
+        ]
+    }
+
+    // `true` if `rect` fits entirely inside this node's boundary.
+    fn fully_contains(&self, rect: &Rectangle<S>) -> bool {
+        rect.x >= self.boundary.x
+            && rect.y >= self.boundary.y
+            && rect.x + rect.w <= self.boundary.x + self.boundary.w
+            && rect.y + rect.h <= self.boundary.y + self.boundary.h
+    }
+
+    /// Insert `item` into the deepest node whose boundary fully contains its
+    /// bounds. An item that straddles a split line is kept at the node where
+    /// the straddle was discovered, since no single child fully contains it.
+    pub fn insert(&mut self, item: &T) -> bool {
+        let bounds = item.bounds();
+        if !self.fully_contains(&bounds) {
+            return false;
+        }
+
+        if self.children.is_none() {
+            if self.items.len() < self.capacity {
+                self.items.push(item.clone());
+                return true;
+            }
+            let (b, c) = (self.boundary, self.capacity);
+            self.children = Some(Self::subdivide(b, c));
+        }
+
+        if let Some(children) = self.children.as_mut() {
+            if let Some(child) = children.iter_mut().find(|c| c.fully_contains(&bounds)) {
+                return child.insert(item);
+            }
+        }
+
+        self.items.push(item.clone());
+        true
+    }
+
+    /// Removes the first item equal to `item`.
+    pub fn remove(&mut self, item: &T) -> bool {
+        if let Some(idx) = self.items.iter().position(|i| i == item) {
+            self.items.remove(idx);
+            return true;
+        }
+        if let Some(children) = self.children.as_mut() {
+            for child in children.iter_mut() {
+                if child.remove(item) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Calls `func` with every item whose bounds intersect `range`. A `None`
+    /// range visits every item in the tree.
+    pub fn query<R: QueryRegion<S>, F: FnMut(&T)>(&self, range: Option<&R>, func: &mut F) {
+        if let Some(range) = range {
+            if !range.intersects_rect(&self.boundary) {
+                return;
+            }
+        }
+
+        for item in &self.items {
+            if range.is_none_or(|range| range.intersects_rect(&item.bounds())) {
+                func(item);
+            }
+        }
+
+        if let Some(children) = self.children.as_ref() {
+            children.iter().for_each(|c| c.query(range, func));
+        }
+    }
+
+    /// Return the total number of items in the BoundsQuadTree.
+    pub fn len(&self) -> usize {
+        self.items.len()
+            + self
+            .children
+            .as_ref()
+            .map(|c| c.iter().fold(0, |acc, c| acc + c.len()))
+            .unwrap_or(0)
+    }
+
+    /// Return `true` if empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,9 +1,13 @@
-use crate::{Rectangle, Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 
-/// A trait to describe Vector x and y to QuadTree.
-pub trait Vector<Rhs = Self>: Clone + PartialEq + std::fmt::Debug {
+use crate::scalar::diff;
+use crate::{QueryRegion, Rectangle, Scalar, Serialize, Deserialize};
+
+/// A trait to describe Vector x and y to QuadTree, over a scalar coordinate type `S`.
+pub trait Vector<S: Scalar = u32, Rhs = Self>: Clone + PartialEq + std::fmt::Debug {
     /// Pulls point out of Type.
-    fn as_point(&self) -> (u32, u32);
+    fn as_point(&self) -> (S, S);
 }
 
 
@@ -16,19 +20,19 @@ pub trait Vector<Rhs = Self>: Clone + PartialEq + std::fmt::Debug {
 ///     x: u32,
 ///     y: u32,
 /// }
-/// 
+///
 /// impl Foo {
 ///     fn new(x: u32, y: u32, item: &str) -> Self {
 ///         Self { item: item.to_string(), x, y }
 ///     }
 /// }
-/// 
+///
 /// impl Vector for Foo {
 ///     fn as_point(&self) -> (u32, u32) {
 ///         (self.x, self.y)
 ///     }
 /// }
-/// 
+///
 /// impl PartialEq for Foo {
 ///     fn eq(&self, other: &Foo) -> bool {
 ///         self.x == other.x && self.y == other.y
@@ -48,7 +52,7 @@ pub trait Vector<Rhs = Self>: Clone + PartialEq + std::fmt::Debug {
 ///
 ///     let mut result = Vec::new();
 ///
-///     qt.query(None, &mut |e| result.push(e.clone()));
+///     qt.query(None::<&Rectangle>, &mut |e| result.push(e.clone()));
 ///
 ///     assert_eq!(result, vec![old.clone()]);
 ///     assert_eq!(qt.len(), 1);
@@ -58,20 +62,28 @@ pub trait Vector<Rhs = Self>: Clone + PartialEq + std::fmt::Debug {
 ///     assert_eq!(Some(old.clone()), return_of_replace);
 ///     assert_eq!(qt.len(), 1);
 ///
-///     qt.query(None, &mut |inner_item| {
+///     qt.query(None::<&Rectangle>, &mut |inner_item| {
 ///         assert_eq!(inner_item, &new);
 ///     });
 /// }
 /// ```
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct QuadTree<T: Vector> {
-    boundary: Rectangle,
+pub struct QuadTree<T, S = u32>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    boundary: Rectangle<S>,
     capacity: usize,
     points: Vec<T>,
-    children: Option<[Box<QuadTree<T>>; 4]>,
+    children: Option<[Box<QuadTree<T, S>>; 4]>,
 }
 
-impl<'a, T: Vector> QuadTree<T> {
+impl<'a, T, S> QuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
     /// Create a new QuadTree object with a boundary and a capacity.
     /// ```rust
     /// # use ezquadtree::{Rectangle, Vector, QuadTree};
@@ -81,19 +93,19 @@ impl<'a, T: Vector> QuadTree<T> {
     /// #     x: u32,
     /// #     y: u32,
     /// # }
-    /// # 
+    /// #
     /// # impl MyType {
     /// #     fn new(x: u32, y: u32, item: &str) -> Self {
     /// #         Self { item: item.to_string(), x, y }
     /// #     }
     /// # }
-    /// # 
+    /// #
     /// # impl Vector for MyType {
     /// #     fn as_point(&self) -> (u32, u32) {
     /// #         (self.x, self.y)
     /// #     }
     /// # }
-    /// # 
+    /// #
     /// # impl PartialEq for MyType {
     /// #     fn eq(&self, other: &MyType) -> bool {
     /// #         self.x == other.x && self.y == other.y
@@ -103,27 +115,31 @@ impl<'a, T: Vector> QuadTree<T> {
     /// let quadtree: QuadTree<MyType> = QuadTree::new(Rectangle::new(0, 0, 40, 40), 4);
     /// # }
     /// ```
-    pub fn new(boundary: Rectangle, capacity: usize) -> Self {
+    pub fn new(boundary: Rectangle<S>, capacity: usize) -> Self {
         Self {
             boundary,
             capacity,
-            points: Vec::with_capacity(capacity as usize),
+            points: Vec::with_capacity(capacity),
             children: None,
         }
     }
 
     // When Nodes(QuadTree) capacity is reached, subdivide is call to create
     // children.of Node(QuadTree).
-    fn subdivide(boundary: Rectangle, capacity: usize) -> [Box<QuadTree<T>>; 4] {
+    fn subdivide(boundary: Rectangle<S>, capacity: usize) -> [Box<QuadTree<T, S>>; 4] {
         let x = boundary.x;
         let y = boundary.y;
-        let w = boundary.w / 2;
-        let h = boundary.h / 2;
+        let w = boundary.w.half();
+        let h = boundary.h.half();
+        // The east/south quadrants take whatever is left over so an odd width or
+        // height still tiles the parent exactly, with no gap column/row dropped.
+        let w2 = boundary.w - w;
+        let h2 = boundary.h - h;
 
         let nw = Rectangle::new(x, y, w, h);
-        let ne = Rectangle::new(x + w, y, w, h);
-        let sw = Rectangle::new(x, y + h, w, h);
-        let se = Rectangle::new(x + w, y + h, w, h);
+        let ne = Rectangle::new(x + w, y, w2, h);
+        let sw = Rectangle::new(x, y + h, w, h2);
+        let se = Rectangle::new(x + w, y + h, w2, h2);
 
         [
             Box::new(QuadTree::new(nw, capacity)),
@@ -162,7 +178,7 @@ impl<'a, T: Vector> QuadTree<T> {
     /// # quadtree.insert(&item1);
     /// let item = Foo { x: 10, y: 5, item: "thing".to_string() };
     /// quadtree.replace(&item);
-    /// # quadtree.query(None, &mut |i| {
+    /// # quadtree.query(None::<&Rectangle>, &mut |i| {
     /// #     assert_eq!(i, &item1);
     /// # });
     /// # }
@@ -189,7 +205,7 @@ impl<'a, T: Vector> QuadTree<T> {
             return false;
         }
 
-        if self.points.len() < self.capacity as usize && !self.points.contains(item) {
+        if self.points.len() < self.capacity && !self.points.contains(item) {
             self.points.push(item.clone());
             return true;
         }
@@ -219,25 +235,190 @@ impl<'a, T: Vector> QuadTree<T> {
         false
     }
 
-    /// Not yet implemented.
-    pub fn query_mut<F: FnMut(&mut T)>(&mut self, _range: &Rectangle, _func: &mut F) {
-        todo!();
-    }
+    /// Like [`query`](QuadTree::query), but calls `func` with `&mut T`.
+    pub fn query_mut<R: QueryRegion<S>, F: FnMut(&mut T)>(&mut self, range: Option<&R>, func: &mut F) {
+        let range = match range {
+            Some(range) => {
+                if !range.intersects_rect(&self.boundary) {
+                    return;
+                }
+                Some(range)
+            }
+            None => None,
+        };
+
+        for p in &mut self.points {
+            if range.is_none_or(|range| range.contains_point(p.as_point())) {
+                func(p);
+            }
+        }
 
-    /// Can pull out Points from a Rectangle area.
-    pub fn query<F: FnMut(&T)>(&self, range: Option<&Rectangle>, func: &mut F) {
-        let range = range.unwrap_or(&self.boundary);
-        if !range.intersects(&self.boundary) {
-            return;
+        if let Some(c) = self.children.as_mut() {
+            c.iter_mut().for_each(|c| c.query_mut(range, func));
         }
+    }
+
+    /// Can pull out Points from any `QueryRegion` area, e.g. a `Rectangle` or a `Circle`.
+    /// A `None` range visits every point in the tree.
+    pub fn query<R: QueryRegion<S>, F: FnMut(&T)>(&self, range: Option<&R>, func: &mut F) {
+        let range = match range {
+            Some(range) => {
+                if !range.intersects_rect(&self.boundary) {
+                    return;
+                }
+                Some(range)
+            }
+            None => None,
+        };
 
         for p in &self.points {
-            if range.contains(p) {
+            if range.is_none_or(|range| range.contains_point(p.as_point())) {
                 func(p);
             }
         }
 
-        if let Some(c) = self.children.as_ref() { c.iter().for_each(|c| c.query(Some(&range), func)) }
+        if let Some(c) = self.children.as_ref() {
+            c.iter().for_each(|c| c.query(range, func));
+        }
+    }
+
+    /// Counts the items in `region` without collecting them into a `Vec` first.
+    /// A node fully contained in `region` adds its `len()` without testing any
+    /// of its points (`len()` still walks that node's subtree, so this is not
+    /// O(1)); a node with no overlap is pruned entirely; only a
+    /// partially-overlapping node tests its points individually.
+    pub fn count_in<R: QueryRegion<S>>(&self, region: &R) -> usize {
+        if !region.intersects_rect(&self.boundary) {
+            return 0;
+        }
+        if region.contains_rect(&self.boundary) {
+            return self.len();
+        }
+
+        let mut count = self
+            .points
+            .iter()
+            .filter(|p| region.contains_point(p.as_point()))
+            .count();
+
+        if let Some(children) = self.children.as_ref() {
+            count += children.iter().map(|c| c.count_in(region)).sum::<usize>();
+        }
+        count
+    }
+
+    /// Folds `f` over every item in `region` without collecting them into a
+    /// `Vec` first. A node with no overlap is pruned entirely; a node fully
+    /// contained in `region` skips the per-point containment test since every
+    /// point it holds is already known to qualify.
+    pub fn reduce_in<R: QueryRegion<S>, A>(
+        &self,
+        region: &R,
+        init: A,
+        mut f: impl FnMut(A, &T) -> A,
+    ) -> A {
+        self.reduce_in_inner(region, init, &mut f)
+    }
+
+    fn reduce_in_inner<R: QueryRegion<S>, A>(
+        &self,
+        region: &R,
+        init: A,
+        f: &mut impl FnMut(A, &T) -> A,
+    ) -> A {
+        if !region.intersects_rect(&self.boundary) {
+            return init;
+        }
+        let fully_contained = region.contains_rect(&self.boundary);
+
+        let mut acc = init;
+        for p in &self.points {
+            if fully_contained || region.contains_point(p.as_point()) {
+                acc = f(acc, p);
+            }
+        }
+
+        if let Some(children) = self.children.as_ref() {
+            for child in children.iter() {
+                acc = child.reduce_in_inner(region, acc, f);
+            }
+        }
+        acc
+    }
+
+    /// Returns the `k` items closest to `point`, ordered nearest-first.
+    ///
+    /// Uses a best-first traversal backed by a binary heap instead of scanning every
+    /// point: nodes are visited in order of their lower-bound squared distance to
+    /// `point`, and once `k` candidates have been found, any node whose boundary is
+    /// farther away than the current worst candidate is skipped entirely.
+    /// ```rust
+    /// # use ezquadtree::{QuadTree, Vector, Rectangle};
+    /// # #[derive(Debug, Clone)]
+    /// # struct Foo { x: u32, y: u32 }
+    /// # impl Vector for Foo {
+    /// #     fn as_point(&self) -> (u32, u32) { (self.x, self.y) }
+    /// # }
+    /// # impl PartialEq for Foo {
+    /// #     fn eq(&self, other: &Foo) -> bool { self.x == other.x && self.y == other.y }
+    /// # }
+    /// # fn main() {
+    /// let mut qt = QuadTree::new(Rectangle::new(0, 0, 40, 40), 4);
+    /// qt.insert(&Foo { x: 1, y: 1 });
+    /// qt.insert(&Foo { x: 30, y: 30 });
+    /// let nearest = qt.nearest((0, 0), 1);
+    /// assert_eq!(nearest.len(), 1);
+    /// # }
+    /// ```
+    pub fn nearest(&self, point: (S, S), k: usize) -> Vec<&T> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut nodes = BinaryHeap::new();
+        nodes.push(NodeEntry {
+            dist: dist_sq_to_rect(point, &self.boundary),
+            node: self,
+        });
+
+        let mut best: BinaryHeap<CandidateEntry<T, S>> = BinaryHeap::new();
+
+        while let Some(NodeEntry { dist, node }) = nodes.pop() {
+            if best.len() == k && best.peek().is_some_and(|worst| dist > worst.dist) {
+                break;
+            }
+
+            for p in &node.points {
+                let d = dist_sq_to_point(point, p.as_point());
+                if best.len() < k {
+                    best.push(CandidateEntry { dist: d, item: p });
+                } else if best.peek().is_some_and(|worst| d < worst.dist) {
+                    best.pop();
+                    best.push(CandidateEntry { dist: d, item: p });
+                }
+            }
+
+            if let Some(children) = node.children.as_ref() {
+                for child in children.iter() {
+                    let d = dist_sq_to_rect(point, &child.boundary);
+                    if best.len() < k || best.peek().is_none_or(|worst| d <= worst.dist) {
+                        nodes.push(NodeEntry { dist: d, node: child });
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec().into_iter().map(|c| c.item).collect()
+    }
+
+    /// The root node's boundary.
+    pub(crate) fn boundary(&self) -> Rectangle<S> {
+        self.boundary
+    }
+
+    /// The per-node point capacity before a node subdivides.
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
     }
 
     /// Return the total number of items in QuadTree
@@ -266,76 +447,261 @@ impl<'a, T: Vector> QuadTree<T> {
         false
     }
 
-    /// Not yet implemented.
-    pub fn iter(&'a self) { // -> Iter<'a, T> {
-        // Iter {
-        //     tree: self,
-        //     found: Vec::new(),
-        //     index: 0,
-        // }
-        todo!();
+    /// Returns an iterator that yields every point in the tree in depth-first
+    /// order: a node's own points first, then each child in turn.
+    pub fn iter(&'a self) -> Iter<'a, T, S> {
+        Iter {
+            stack: vec![(self, 0)],
+        }
     }
 
-    /// Not yet implemented.
-    pub fn iter_mut() {
-        todo!();
+    /// Like [`iter`](QuadTree::iter), but yields `&mut T`.
+    pub fn iter_mut(&'a mut self) -> IterMut<'a, T, S> {
+        IterMut {
+            stack: vec![(self as *mut Self, 0)],
+            _marker: std::marker::PhantomData,
+        }
     }
+}
+
 
-    /// Not yet implemented.
-    pub fn into_iter() {
-        todo!();
+// A node queued for best-first `nearest` traversal, ordered so the heap pops the
+// closest node first (smallest `dist` = highest priority).
+struct NodeEntry<'a, T: Vector<S>, S: Scalar> {
+    dist: S,
+    node: &'a QuadTree<T, S>,
+}
+
+impl<'a, T: Vector<S>, S: Scalar> PartialEq for NodeEntry<'a, T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
     }
 }
 
+impl<'a, T: Vector<S>, S: Scalar> Eq for NodeEntry<'a, T, S> {}
+
+impl<'a, T: Vector<S>, S: Scalar> PartialOrd for NodeEntry<'a, T, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T: Vector<S>, S: Scalar> Ord for NodeEntry<'a, T, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist.partial_cmp(&self.dist).expect("scalar distances should be comparable")
+    }
+}
+
+// A candidate result for `nearest`, ordered so the heap's max is the worst
+// (farthest) candidate currently held, making it cheap to evict.
+struct CandidateEntry<'a, T: Vector<S>, S: Scalar> {
+    dist: S,
+    item: &'a T,
+}
+
+impl<'a, T: Vector<S>, S: Scalar> PartialEq for CandidateEntry<'a, T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl<'a, T: Vector<S>, S: Scalar> Eq for CandidateEntry<'a, T, S> {}
+
+impl<'a, T: Vector<S>, S: Scalar> PartialOrd for CandidateEntry<'a, T, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T: Vector<S>, S: Scalar> Ord for CandidateEntry<'a, T, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.partial_cmp(&other.dist).expect("scalar distances should be comparable")
+    }
+}
+
+// Squared distance between two points. Uses `diff` rather than plain
+// subtraction so it never underflows for unsigned scalar types.
+fn dist_sq_to_point<S: Scalar>(a: (S, S), b: (S, S)) -> S {
+    let dx = diff(a.0, b.0);
+    let dy = diff(a.1, b.1);
+    dx * dx + dy * dy
+}
+
+// Squared distance from a point to the nearest edge of a rectangle, or zero if
+// the point is inside it. Each axis is clamped to zero separately so a point
+// that is left/above the boundary never underflows an unsigned scalar type.
+fn dist_sq_to_rect<S: Scalar>(point: (S, S), rect: &Rectangle<S>) -> S {
+    let dx = if point.0 < rect.x {
+        rect.x - point.0
+    } else if point.0 > rect.x + rect.w {
+        point.0 - (rect.x + rect.w)
+    } else {
+        S::zero()
+    };
+
+    let dy = if point.1 < rect.y {
+        rect.y - point.1
+    } else if point.1 > rect.y + rect.h {
+        point.1 - (rect.y + rect.h)
+    } else {
+        S::zero()
+    };
+
+    dx * dx + dy * dy
+}
+
+// Depth-first, non-collecting iterator over `&T`: an explicit stack of
+// (node, next point index) pairs stands in for the call stack a recursive
+// walk would use, advancing into a node's children only once its own points
+// are exhausted.
+pub struct Iter<'a, T, S = u32>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    stack: Vec<(&'a QuadTree<T, S>, usize)>,
+}
+
+impl<'a, T, S> Iterator for Iter<'a, T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    type Item = &'a T;
 
-/*
-impl<T: Vector> Iterator for &QuadTree<T> {
-    type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, index)) = self.stack.last_mut() {
+            if let Some(item) = node.points.get(*index) {
+                *index += 1;
+                return Some(item);
+            }
+
+            let node = *node;
+            self.stack.pop();
+            if let Some(children) = node.children.as_ref() {
+                for child in children.iter().rev() {
+                    self.stack.push((child, 0));
+                }
+            }
+        }
         None
     }
 }
-*/
 
-/*
-impl<'a, T: Vector> IntoIterator for &'a QuadTree<T> {
+impl<'a, T, S> IntoIterator for &'a QuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
     type Item = &'a T;
-    type IntoIter = Iter<'a, T>;
+    type IntoIter = Iter<'a, T, S>;
+
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-pub struct Iter<'a, T> where T: Vector + 'a {
-    tree: &'a QuadTree<T>,
-    found: Vec<&'a T>,
-    index: usize,
-    none_count: usize,
+// Mutable counterpart to `Iter`. Each node on the stack is visited by exactly
+// one stack entry at a time, so the raw pointer it holds can be safely
+// dereferenced into a unique `&'a mut` without aliasing another reference
+// this iterator has already handed out.
+pub struct IterMut<'a, T, S = u32>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    stack: Vec<(*mut QuadTree<T, S>, usize)>,
+    _marker: std::marker::PhantomData<&'a mut QuadTree<T, S>>,
 }
 
-impl<'a, T: Vector> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+impl<'a, T, S> Iterator for IterMut<'a, T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    type Item = &'a mut T;
+
     fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node_ptr, index)) = self.stack.last_mut() {
+            // SAFETY: see the comment on `IterMut`.
+            let node = unsafe { &mut **node_ptr };
+            if let Some(item) = node.points.get_mut(*index) {
+                *index += 1;
+                return Some(item);
+            }
+
+            self.stack.pop();
+            if let Some(children) = node.children.as_mut() {
+                for child in children.iter_mut().rev() {
+                    self.stack.push((child.as_mut() as *mut QuadTree<T, S>, 0));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a mut QuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
-*/
-/*
-use std::iter::Iterator;
-enum QuadTree {
-    Internal(Box<[QuadTree; 4]>),
-    Leaf(Option<u32>),
+
+// Owning depth-first iterator: the stack holds each pending subtree's point
+// iterator alongside its (not yet visited) children, so points are moved out
+// without ever collecting the whole tree into a `Vec` up front.
+type IntoIterFrame<T, S> = (std::vec::IntoIter<T>, Option<[Box<QuadTree<T, S>>; 4]>);
+
+pub struct IntoIter<T, S = u32>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    stack: Vec<IntoIterFrame<T, S>>,
 }
 
-impl QuadTree {
-    fn into_iter<'a>(&'a self) -> Box<dyn Iterator<Item=&'a u32> + 'a> {
-        match self {
-            QuadTree::Internal(children) => children.iter()
-                .fold(
-                    Box::new(std::iter::empty()) as Box<dyn Iterator<Item = _>>,
-                    |i, c| Box::new(i.chain(c.into_iter()))
-                    ),
-            QuadTree::Leaf(points) => Box::new(points.iter())
+impl<T, S> Iterator for IntoIter<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((points, _)) = self.stack.last_mut() {
+            if let Some(item) = points.next() {
+                return Some(item);
+            }
+
+            let (_, children) = self.stack.pop().expect("just peeked");
+            if let Some(children) = children {
+                for child in children.into_iter().rev() {
+                    self.stack.push((child.points.into_iter(), child.children));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T, S> IntoIterator for QuadTree<T, S>
+where
+    T: Vector<S>,
+    S: Scalar,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            stack: vec![(self.points.into_iter(), self.children)],
         }
     }
 }
-*/
@@ -0,0 +1,54 @@
+use std::ops::{Add, Mul, Sub};
+
+/// A coordinate type usable as a `QuadTree` scalar.
+///
+/// Provides just enough arithmetic to subdivide a boundary and measure
+/// distances: copy semantics, ordering, `+`/`-`/`*`, a `zero` value, and a
+/// `half` used when splitting a node's width/height into quadrants.
+pub trait Scalar:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + std::fmt::Debug
+{
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// Half of this value, used to split a boundary into quadrants.
+    fn half(self) -> Self;
+}
+
+macro_rules! impl_scalar_int {
+    ($($t:ty),*) => {
+        $(
+            impl Scalar for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn half(self) -> Self {
+                    self / 2
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_int!(u32, i64);
+
+impl Scalar for f32 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn half(self) -> Self {
+        self / 2.0
+    }
+}
+
+/// Absolute difference between two scalars, without relying on signed
+/// subtraction or a separate `abs`, so it works for unsigned types too.
+pub(crate) fn diff<S: Scalar>(a: S, b: S) -> S {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{Serialize, Deserialize, Vector};
+use crate::scalar::diff;
+use crate::{Serialize, Deserialize, Scalar, Vector};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 /// Rectangle is to represent a rectangular bounding box.
@@ -6,55 +7,84 @@ use crate::{Serialize, Deserialize, Vector};
 /// ```
 /// # use ezquadtree::Rectangle;
 /// # fn main() {
-/// // for now it only takes a u32 but At some point will be a T.
-/// let rect = Rectangle::new(0, 0, 40, 40);
+/// let rect: Rectangle = Rectangle::new(0, 0, 40, 40);
 /// # }
 /// ```
-pub struct Rectangle {
-    pub x: u32,
-    pub y: u32,
-    pub w: u32,
-    pub h: u32,
+pub struct Rectangle<S = u32> {
+    pub x: S,
+    pub y: S,
+    pub w: S,
+    pub h: S,
 }
 
-impl Rectangle {
+/// A region that can be used to drive a `QuadTree` query, whether that's a
+/// `Rectangle` range or a `Circle` radius search.
+pub trait QueryRegion<S: Scalar = u32> {
+    /// Returns `true` if `p` falls inside the region.
+    fn contains_point(&self, p: (S, S)) -> bool;
+
+    /// Returns `true` if the region overlaps any part of `r`, used to prune
+    /// child nodes whose boundary can't possibly hold a match.
+    fn intersects_rect(&self, r: &Rectangle<S>) -> bool;
+
+    /// Returns `true` if `r` falls entirely inside the region, letting a
+    /// quadtree skip per-point containment tests for a node known to fully
+    /// qualify. The default checks every corner of `r`, including the far
+    /// corners at `x + w`/`y + h`. For a convex region (both `Rectangle` and
+    /// `Circle` qualify) a `true` answer is always correct, but the check is
+    /// conservative: a `Rectangle` region never contains its own far edge, so
+    /// it returns `false` for a rect identical to itself.
+    fn contains_rect(&self, r: &Rectangle<S>) -> bool {
+        let corners = [
+            (r.x, r.y),
+            (r.x + r.w, r.y),
+            (r.x, r.y + r.h),
+            (r.x + r.w, r.y + r.h),
+        ];
+        corners.into_iter().all(|p| self.contains_point(p))
+    }
+}
+
+impl<S: Scalar> Rectangle<S> {
     /// Create a new Rectangle.
-    pub fn new(x: u32, y: u32, w: u32, h: u32) -> Self {
+    pub fn new(x: S, y: S, w: S, h: S) -> Self {
         Self { x, y, w, h }
     }
 
-    /// Get the top left most x and y of rectangle.
-    pub fn top_left_corner(&self) -> (u32, u32) {
-        (self.x - self.w / 2, self.y - self.h / 2)
+    /// Get the top left most x and y of rectangle. Clamped to zero rather than
+    /// underflowing when `x`/`y` is smaller than half the width/height, the
+    /// same way `dist_sq_to_rect` clamps its per-axis distance.
+    pub fn top_left_corner(&self) -> (S, S) {
+        let half_w = self.w.half();
+        let half_h = self.h.half();
+        let x = if self.x >= half_w { self.x - half_w } else { S::zero() };
+        let y = if self.y >= half_h { self.y - half_h } else { S::zero() };
+        (x, y)
     }
 
     /// Checks to see if a a given Vector is in the QuadTree.
-    pub fn contains<T>(&self, item: &T) -> bool where T: Vector {
-        let (x, y) = Vector::as_point(item);
-        x >= self.x
-            && x < self.x + self.w
-            && y >= self.y
-            && y < self.y + self.h
+    pub fn contains<T>(&self, item: &T) -> bool where T: Vector<S> {
+        self.contains_point(Vector::as_point(item))
     }
 
     /// Checks to see if any part of another Rectangle overlaps its self.
-    pub fn intersects(&self, range: &Rectangle) -> bool {
+    pub fn intersects(&self, range: &Rectangle<S>) -> bool {
         Self::range_intersects(self.get_range_x(), range.get_range_x())
             && Self::range_intersects(self.get_range_y(), range.get_range_y())
     }
 
     // returns a Range for X.
-    fn get_range_x(&self) -> std::ops::Range<u32> {
+    fn get_range_x(&self) -> std::ops::Range<S> {
         self.x..(self.x + self.w)
     }
 
     // returns a Range for Y.
-    fn get_range_y(&self) -> std::ops::Range<u32> {
+    fn get_range_y(&self) -> std::ops::Range<S> {
         self.y..(self.y + self.h)
     }
 
     // return true if ranges overlap.
-    fn range_intersects(mut range1: std::ops::Range<u32>, mut range2: std::ops::Range<u32>) -> bool {
+    fn range_intersects(mut range1: std::ops::Range<S>, mut range2: std::ops::Range<S>) -> bool {
         if range1.start > range2.start {
             std::mem::swap(&mut range1, &mut range2);
         }
@@ -62,35 +92,47 @@ impl Rectangle {
     }
 }
 
+impl<S: Scalar> QueryRegion<S> for Rectangle<S> {
+    fn contains_point(&self, p: (S, S)) -> bool {
+        let (x, y) = p;
+        x >= self.x
+            && x < self.x + self.w
+            && y >= self.y
+            && y < self.y + self.h
+    }
+
+    fn intersects_rect(&self, r: &Rectangle<S>) -> bool {
+        self.intersects(r)
+    }
+}
+
 // circle struct for a circle shaped query
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[allow(dead_code)]
-pub struct Circle {
-    x: u32,
-    y: u32,
-    r: u32,
-    r_squared: u32,
+pub struct Circle<S = u32> {
+    x: S,
+    y: S,
+    r: S,
+    r_squared: S,
 }
 
 #[allow(dead_code)]
-impl Circle {
-    pub fn _new(x: u32, y: u32, r: u32) -> Self {
+impl<S: Scalar> Circle<S> {
+    pub fn _new(x: S, y: S, r: S) -> Self {
         let r_squared = r * r;
         Self { x, y, r, r_squared }
     }
 
-    pub fn contains<T>(&self, item: T) -> bool where T: Vector {
+    pub fn contains<T>(&self, item: T) -> bool where T: Vector<S> {
         // check if the point is in the circle by checking if the euclidean distance of
         // the point and the center of the circle if smaller or equal to the radius of
         // the circle
-        let (x, y) = Vector::as_point(&item);
-        let d = (x - self.x).pow(2) + (y - self.y).pow(2);
-        d <= self.r_squared
+        self.contains_point(Vector::as_point(&item))
     }
 
-    pub fn intersects(&self, range: Rectangle) -> bool {
-        let x_dist = ((range.x - self.x) as i32).abs();
-        let y_dist = ((range.y - self.y) as i32).abs();
+    pub fn intersects(&self, range: Rectangle<S>) -> bool {
+        let x_dist = diff(range.x, self.x);
+        let y_dist = diff(range.y, self.y);
 
         // radius of the circle
         let r = self.r;
@@ -98,19 +140,32 @@ impl Circle {
         let w = range.w;
         let h = range.h;
 
-        let edges = (x_dist - w as i32).pow(2) + (y_dist - h as i32).pow(2);
-
         // no intersection
-        if x_dist > (r + w) as i32 || y_dist > (r + h) as i32 {
+        if x_dist > r + w || y_dist > r + h {
             return false;
         }
 
         // intersection within the circle
-        if x_dist <= w as i32 || y_dist <= h as i32 {
+        if x_dist <= w || y_dist <= h {
             return true;
         }
 
         // intersection on the edge of the circle
-        edges <= self.r_squared as i32
+        let edge_x = diff(x_dist, w);
+        let edge_y = diff(y_dist, h);
+        edge_x * edge_x + edge_y * edge_y <= self.r_squared
+    }
+}
+
+impl<S: Scalar> QueryRegion<S> for Circle<S> {
+    fn contains_point(&self, p: (S, S)) -> bool {
+        let (x, y) = p;
+        let dx = diff(x, self.x);
+        let dy = diff(y, self.y);
+        dx * dx + dy * dy <= self.r_squared
+    }
+
+    fn intersects_rect(&self, r: &Rectangle<S>) -> bool {
+        self.intersects(*r)
     }
 }
@@ -1,140 +1,456 @@
-use super::*;
-
-
-#[test]
-fn test_rectangle() {
-    let r = Rectangle::new(10, 10, 50, 50);
-    assert_eq!(r, Rectangle::new(10, 10, 50, 50));
-}
-
-// #[test]
-// fn test_circle() {
-//     let r = Circle::new(10, 10, 50);
-//     assert_eq!(r, Circle::new(10, 10, 50));
-// }
-
-#[test]
-fn test_quadtree_insert_query() {
-
-    let foos = create_foo(0..9);
-    let mut result: Vec<Foo> = Vec::new();
-
-    let (w, h) = (40, 40);
-    let bb = Rectangle::new(0, 0, w, h);
-    let mut qt = QuadTree::new(bb, 4);
-
-    let bb = Rectangle::new(0, 0, w+10, h+10);
-
-    insert_foo(&mut qt, &foos);
-
-    qt.query(Some(&bb), &mut |e| result.push(e.clone()));
-
-    assert_eq!(result, foos);
-}
-
-#[test]
-fn test_len() {
-    let foos = create_foo(0..9);
-
-    let (w, h) = (40, 40);
-    let bb = Rectangle::new(0, 0, w, h);
-
-    let mut qt = QuadTree::new(bb.clone(), 4);
-
-    insert_foo(&mut qt, &foos);
-
-    assert_eq!(qt.len(), 9);
-}
-
-#[test]
-fn test_insert_same_location() {
-    let old = Foo::new(5, 5, "old");
-    let new = Foo::new(5, 5, "new");
-
-    let (w, h) = (40, 40);
-    let bb = Rectangle::new(0, 0, w, h);
-
-    let mut qt = QuadTree::new(bb, 4);
-
-    qt.insert(&old);
-    qt.insert(&new);
-
-    let mut result = Vec::new();
-
-    qt.query(None, &mut |e| result.push(e.clone()));
-
-    assert_eq!(result, vec![old]);
-    assert_eq!(qt.len(), 1);
-}
-
-#[test]
-fn test_replace_same_location() {
-    let old = Foo::new(5, 5, "old");
-    let new = Foo::new(5, 5, "new");
-
-    let (w, h) = (40, 40);
-    let bb = Rectangle::new(0, 0, w, h);
-
-    let mut qt = QuadTree::new(bb, 4);
-
-    qt.insert(&old);
-    let return_of_replace = qt.replace(&new);
-
-    let mut result = Vec::new();
-
-    qt.query(None, &mut |e| result.push(e.clone()));
-
-    // assert_eq!(Some(old), return_of_replace);
-    // assert_eq!(result, vec![new]);
-    // assert_eq!(qt.len(), 1);
-}
-
-#[test]
-fn test_iter() {
-    let foos = create_foo(0..100);
-    let (w, h) = (400, 400);
-    let bb = Rectangle::new(0, 0, w, h);
-
-    let mut qt = QuadTree::new(bb, 4);
-
-    insert_foo(&mut qt, &foos);
-
-    println!("Starting For Loop");
-    // for (idx, item) in qt.iter().enumerate() {
-    //     dbg!(item, &foos[idx]);
-    //     assert_eq!(item, &foos[idx]);
-    // }
-    println!("Ending For Loop");
-}
-#[derive(Debug, Clone, PartialEq)]
-struct Foo {
-    item: String,
-    x: u32,
-    y: u32,
-}
-
-impl Foo {
-    fn new(x: u32, y: u32, item: &str) -> Self {
-        Self { item: item.to_string(), x, y }
-    }
-}
-
-impl Vector for Foo {
-    fn as_point(&self) -> (u32, u32) {
-        (self.x, self.y)
-    }
-}
-
-fn create_foo(range: std::ops::Range<u32>) -> Vec<Foo> {
-    let mut foos = Vec::new();
-    for i in range {
-        foos.push(Foo::new(i, 0, "FOOOOOOO"));
-    }
-    foos
-}
-
-fn insert_foo(qt: &mut QuadTree<Foo>, foos: &Vec<Foo>) {
-    for f in foos.iter() {
-        qt.insert(f);
-    }
-}
+use super::*;
+
+
+#[test]
+fn test_rectangle() {
+    let r: Rectangle = Rectangle::new(10, 10, 50, 50);
+    assert_eq!(r, Rectangle::new(10, 10, 50, 50));
+}
+
+#[test]
+fn test_top_left_corner_clamps_instead_of_underflowing() {
+    let r: Rectangle = Rectangle::new(0, 0, 40, 40);
+    assert_eq!(r.top_left_corner(), (0, 0));
+}
+
+#[test]
+fn test_scalar_f32() {
+    let bb: Rectangle<f32> = Rectangle::new(0.0, 0.0, 40.0, 40.0);
+    let mut qt: QuadTree<(f32, f32), f32> = QuadTree::new(bb, 4);
+
+    qt.insert(&(1.0, 1.0));
+    qt.insert(&(30.0, 30.0));
+
+    let nearest = qt.nearest((0.0, 0.0), 1);
+    assert_eq!(nearest, vec![&(1.0, 1.0)]);
+}
+
+#[test]
+fn test_scalar_i64() {
+    let bb: Rectangle<i64> = Rectangle::new(0, 0, 40, 40);
+    let mut qt: QuadTree<(i64, i64), i64> = QuadTree::new(bb, 4);
+
+    qt.insert(&(1, 1));
+    qt.insert(&(30, 30));
+
+    let mut result = Vec::new();
+    qt.query(None::<&Rectangle<i64>>, &mut |p| result.push(*p));
+    assert_eq!(result.len(), 2);
+}
+
+// #[test]
+// fn test_circle() {
+//     let r = Circle::new(10, 10, 50);
+//     assert_eq!(r, Circle::new(10, 10, 50));
+// }
+
+#[test]
+fn test_quadtree_odd_boundary_subdivide() {
+    // A 5x5 boundary splits into 2/3-wide quadrants; every point must still
+    // land in some child instead of falling through an uncovered gap. Pins
+    // down the same w2/h2 fix already covered for ArenaQuadTree and
+    // BoundsQuadTree, applied here to the base QuadTree's subdivide.
+    let bb = Rectangle::new(0, 0, 5, 5);
+    let mut qt = QuadTree::new(bb, 1);
+
+    assert!(qt.insert(&Foo::new(0, 0, "a")));
+    assert!(qt.insert(&Foo::new(4, 4, "b")));
+
+    assert_eq!(qt.len(), 2);
+}
+
+#[test]
+fn test_quadtree_insert_query() {
+
+    let foos = create_foo(0..9);
+    let mut result: Vec<Foo> = Vec::new();
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+    let mut qt = QuadTree::new(bb, 4);
+
+    let bb = Rectangle::new(0, 0, w+10, h+10);
+
+    insert_foo(&mut qt, &foos);
+
+    qt.query(Some(&bb), &mut |e| result.push(e.clone()));
+
+    assert_eq!(result, foos);
+}
+
+#[test]
+fn test_len() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb.clone(), 4);
+
+    insert_foo(&mut qt, &foos);
+
+    assert_eq!(qt.len(), 9);
+}
+
+#[test]
+fn test_insert_same_location() {
+    let old = Foo::new(5, 5, "old");
+    let new = Foo::new(5, 5, "new");
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    qt.insert(&old);
+    qt.insert(&new);
+
+    let mut result = Vec::new();
+
+    qt.query(None::<&Rectangle>, &mut |e| result.push(e.clone()));
+
+    assert_eq!(result, vec![old]);
+    assert_eq!(qt.len(), 1);
+}
+
+#[test]
+fn test_replace_same_location() {
+    let old = Foo::new(5, 5, "old");
+    let new = Foo::new(5, 5, "new");
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    qt.insert(&old);
+    let return_of_replace = qt.replace(&new);
+
+    let mut result = Vec::new();
+
+    qt.query(None::<&Rectangle>, &mut |e| result.push(e.clone()));
+
+    // assert_eq!(Some(old), return_of_replace);
+    // assert_eq!(result, vec![new]);
+    // assert_eq!(qt.len(), 1);
+}
+
+#[test]
+fn test_iter() {
+    let foos = create_foo(0..100);
+    let (w, h) = (400, 400);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    println!("Starting For Loop");
+    for (idx, item) in qt.iter().enumerate() {
+        dbg!(item, &foos[idx]);
+        assert_eq!(item, &foos[idx]);
+    }
+    println!("Ending For Loop");
+}
+
+#[test]
+fn test_iter_mut() {
+    let foos = create_foo(0..100);
+    let (w, h) = (400, 400);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    for item in qt.iter_mut() {
+        item.item = "CHANGED".to_string();
+    }
+
+    assert_eq!(qt.len(), foos.len());
+    for item in qt.iter() {
+        assert_eq!(item.item, "CHANGED");
+    }
+}
+
+#[test]
+fn test_into_iter() {
+    let foos = create_foo(0..100);
+    let (w, h) = (400, 400);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    let collected: Vec<Foo> = qt.into_iter().collect();
+    assert_eq!(collected, foos);
+}
+#[test]
+fn test_query_mut() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    qt.query_mut(Some(&Rectangle::new(0, 0, 4, 1)), &mut |e| e.item = "CHANGED".to_string());
+
+    let mut result = Vec::new();
+    qt.query(None::<&Rectangle>, &mut |e| result.push(e.clone()));
+
+    for item in &result[0..4] {
+        assert_eq!(item.item, "CHANGED");
+    }
+    for item in &result[4..9] {
+        assert_eq!(item.item, "FOOOOOOO");
+    }
+}
+
+#[test]
+fn test_query_circle() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    let circle = Circle::_new(0, 0, 3);
+    let mut result = Vec::new();
+
+    qt.query(Some(&circle), &mut |e| result.push(e.clone()));
+
+    assert_eq!(result, foos[0..4]);
+}
+
+#[test]
+fn test_nearest() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    let nearest = qt.nearest((0, 0), 3);
+
+    assert_eq!(nearest, vec![&foos[0], &foos[1], &foos[2]]);
+}
+
+#[test]
+fn test_arena_quadtree_odd_boundary_insert() {
+    // A 5x5 boundary splits into 2/3-wide quadrants; every point must still
+    // land in some child instead of falling through an uncovered gap.
+    let bb = Rectangle::new(0, 0, 5, 5);
+    let mut qt = ArenaQuadTree::new(bb, 1);
+
+    assert!(qt.insert(&Foo::new(0, 0, "a")));
+    assert!(qt.insert(&Foo::new(4, 4, "b")));
+
+    assert_eq!(qt.len(), 2);
+}
+
+#[test]
+fn test_arena_quadtree_insert_same_location() {
+    let old = Foo::new(5, 5, "old");
+    let new = Foo::new(5, 5, "new");
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = ArenaQuadTree::new(bb, 4);
+
+    assert!(qt.insert(&old));
+    assert!(!qt.insert(&new));
+
+    let mut result = Vec::new();
+    qt.query(None::<&Rectangle>, &mut |e| result.push(e.clone()));
+
+    assert_eq!(result, vec![old]);
+    assert_eq!(qt.len(), 1);
+}
+
+#[test]
+fn test_arena_quadtree_insert_query_remove() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+    let mut qt = ArenaQuadTree::new(bb, 4);
+
+    for f in &foos {
+        assert!(qt.insert(f));
+    }
+    assert_eq!(qt.len(), 9);
+
+    let mut result = Vec::new();
+    qt.query(Some(&Rectangle::new(0, 0, w + 10, h + 10)), &mut |e| {
+        result.push(e.clone())
+    });
+    assert_eq!(result, foos);
+
+    assert!(qt.remove(&foos[0]));
+    assert_eq!(qt.len(), 8);
+}
+
+#[test]
+fn test_count_in() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    // fully contains the whole tree
+    assert_eq!(qt.count_in(&Rectangle::new(0, 0, w, h)), 9);
+    // only partially overlaps, so points are tested individually
+    assert_eq!(qt.count_in(&Rectangle::new(0, 0, 4, 1)), 4);
+    // no overlap at all
+    assert_eq!(qt.count_in(&Rectangle::new(30, 30, 5, 5)), 0);
+}
+
+#[test]
+fn test_reduce_in() {
+    let foos = create_foo(0..9);
+
+    let (w, h) = (40, 40);
+    let bb = Rectangle::new(0, 0, w, h);
+
+    let mut qt = QuadTree::new(bb, 4);
+
+    insert_foo(&mut qt, &foos);
+
+    let sum = qt.reduce_in(&Rectangle::new(0, 0, 4, 1), 0u32, |acc, f| acc + f.x);
+    assert_eq!(sum, 1 + 2 + 3);
+}
+
+#[test]
+fn test_bounds_quadtree_odd_boundary_subdivide() {
+    // A 5x5 boundary splits into 2/3-wide quadrants; a sprite sitting in the
+    // "extra" column/row must still be able to descend into a child instead
+    // of being stuck scanning the parent node forever.
+    let bb = Rectangle::new(0, 0, 5, 5);
+    let mut qt = BoundsQuadTree::new(bb, 1);
+
+    qt.insert(&Sprite::new(0, 0, 1, 1));
+    qt.insert(&Sprite::new(4, 4, 1, 1));
+
+    assert_eq!(qt.len(), 2);
+
+    let mut result = Vec::new();
+    qt.query(Some(&Rectangle::new(4, 4, 1, 1)), &mut |s| result.push(s.clone()));
+    assert_eq!(result, vec![Sprite::new(4, 4, 1, 1)]);
+}
+
+#[test]
+fn test_bounds_quadtree_query() {
+    let sprites = vec![
+        Sprite::new(0, 0, 4, 4),
+        Sprite::new(10, 10, 4, 4),
+        Sprite::new(30, 30, 4, 4),
+    ];
+
+    let bb = Rectangle::new(0, 0, 40, 40);
+    let mut qt = BoundsQuadTree::new(bb, 1);
+
+    for s in &sprites {
+        qt.insert(s);
+    }
+
+    assert_eq!(qt.len(), 3);
+
+    let range = Rectangle::new(0, 0, 20, 20);
+    let mut result = Vec::new();
+
+    qt.query(Some(&range), &mut |s| result.push(s.clone()));
+
+    assert_eq!(result, sprites[0..2]);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Sprite {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+impl Sprite {
+    fn new(x: u32, y: u32, w: u32, h: u32) -> Self {
+        Self { x, y, w, h }
+    }
+}
+
+impl Bounded for Sprite {
+    fn bounds(&self) -> Rectangle {
+        Rectangle::new(self.x, self.y, self.w, self.h)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Foo {
+    item: String,
+    x: u32,
+    y: u32,
+}
+
+impl Foo {
+    fn new(x: u32, y: u32, item: &str) -> Self {
+        Self { item: item.to_string(), x, y }
+    }
+}
+
+// Same-location items are the same point as far as the tree is concerned, so
+// equality (and therefore `insert`'s dedup check) only looks at x/y, matching
+// the `QuadTree` doc example's `Foo`.
+impl PartialEq for Foo {
+    fn eq(&self, other: &Foo) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+impl Vector for Foo {
+    fn as_point(&self) -> (u32, u32) {
+        (self.x, self.y)
+    }
+}
+
+// A bare coordinate tuple is its own point, letting `test_scalar_f32`/
+// `test_scalar_i64` exercise `QuadTree` over non-`u32` scalars without a
+// dedicated item type.
+impl Vector<f32> for (f32, f32) {
+    fn as_point(&self) -> (f32, f32) {
+        *self
+    }
+}
+
+impl Vector<i64> for (i64, i64) {
+    fn as_point(&self) -> (i64, i64) {
+        *self
+    }
+}
+
+fn create_foo(range: std::ops::Range<u32>) -> Vec<Foo> {
+    let mut foos = Vec::new();
+    for i in range {
+        foos.push(Foo::new(i, 0, "FOOOOOOO"));
+    }
+    foos
+}
+
+fn insert_foo(qt: &mut QuadTree<Foo>, foos: &Vec<Foo>) {
+    for f in foos.iter() {
+        qt.insert(f);
+    }
+}